@@ -0,0 +1,498 @@
+//! Low-level creation, inspection and removal of NTFS reparse points.
+//!
+//! This talks to the kernel directly via `DeviceIoControl` and the
+//! `FSCTL_{SET,GET,DELETE}_REPARSE_POINT` control codes, building and
+//! parsing the `REPARSE_DATA_BUFFER` layouts described at
+//! <https://docs.microsoft.com/en-us/windows/win32/fileio/reparse-points>.
+
+use std::ffi::{OsStr, OsString};
+use std::fs::{self, File, OpenOptions};
+use std::io;
+use std::mem;
+use std::os::windows::ffi::{OsStrExt, OsStringExt};
+use std::os::windows::fs::{MetadataExt, OpenOptionsExt};
+use std::os::windows::io::AsRawHandle;
+use std::path::{Path, PathBuf};
+use std::ptr;
+
+use winapi::shared::minwindef::{DWORD, WORD};
+use winapi::um::ioapiset::DeviceIoControl;
+use winapi::um::winbase::{FILE_FLAG_BACKUP_SEMANTICS, FILE_FLAG_OPEN_REPARSE_POINT};
+use winapi::um::winioctl::{
+    FSCTL_DELETE_REPARSE_POINT, FSCTL_GET_REPARSE_POINT, FSCTL_SET_REPARSE_POINT,
+};
+use winapi::um::winnt::{
+    FILE_ATTRIBUTE_DIRECTORY, FILE_ATTRIBUTE_REPARSE_POINT, IO_REPARSE_TAG_MOUNT_POINT,
+    IO_REPARSE_TAG_SYMLINK,
+};
+
+/// Largest buffer the kernel will ever fill in for a single reparse point.
+const MAXIMUM_REPARSE_DATA_BUFFER_SIZE: usize = 16 * 1024;
+
+/// Not exposed by the `winnt` module of the `winapi` crate we depend on.
+const SYMLINK_FLAG_RELATIVE: DWORD = 0x1;
+
+/// Header of the `IO_REPARSE_TAG_MOUNT_POINT` `REPARSE_DATA_BUFFER` layout,
+/// including the `SubstituteName`/`PrintName` offsets+lengths; the path
+/// buffer follows it.
+#[allow(non_snake_case)]
+#[repr(C)]
+struct MountPointHeader {
+    ReparseTag: DWORD,
+    ReparseDataLength: WORD,
+    Reserved: WORD,
+    SubstituteNameOffset: WORD,
+    SubstituteNameLength: WORD,
+    PrintNameOffset: WORD,
+    PrintNameLength: WORD,
+}
+
+/// Same as `MountPointHeader`, except `IO_REPARSE_TAG_SYMLINK` carries an
+/// extra `Flags` field ahead of the path buffer.
+#[allow(non_snake_case)]
+#[repr(C)]
+struct SymlinkHeader {
+    ReparseTag: DWORD,
+    ReparseDataLength: WORD,
+    Reserved: WORD,
+    SubstituteNameOffset: WORD,
+    SubstituteNameLength: WORD,
+    PrintNameOffset: WORD,
+    PrintNameLength: WORD,
+    Flags: DWORD,
+}
+
+fn to_u16s<S: AsRef<OsStr>>(s: S) -> Vec<u16> {
+    s.as_ref().encode_wide().collect()
+}
+
+fn push_u16s(buf: &mut Vec<u8>, s: &[u16]) {
+    buf.extend(s.iter().flat_map(|c| c.to_le_bytes()));
+}
+
+fn header_bytes<T>(header: &T) -> &[u8] {
+    unsafe { std::slice::from_raw_parts(header as *const T as *const u8, mem::size_of::<T>()) }
+}
+
+/// Opens `path` itself rather than whatever it resolves to, which is
+/// required in order to read or write its reparse data.
+fn open_reparse_point(path: &Path, write: bool) -> io::Result<File> {
+    OpenOptions::new()
+        .read(!write)
+        .write(write)
+        .custom_flags(FILE_FLAG_OPEN_REPARSE_POINT | FILE_FLAG_BACKUP_SEMANTICS)
+        .open(path)
+}
+
+fn set_reparse_point(file: &File, buf: &mut [u8]) -> io::Result<()> {
+    let mut returned = 0;
+    let ok = unsafe {
+        DeviceIoControl(
+            file.as_raw_handle() as _,
+            FSCTL_SET_REPARSE_POINT,
+            buf.as_mut_ptr() as *mut _,
+            buf.len() as DWORD,
+            ptr::null_mut(),
+            0,
+            &mut returned,
+            ptr::null_mut(),
+        )
+    };
+    if ok == 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+/// Reads the raw `REPARSE_DATA_BUFFER` bytes for `file`.
+fn get_reparse_data(file: &File) -> io::Result<Vec<u8>> {
+    let mut buf = vec![0u8; MAXIMUM_REPARSE_DATA_BUFFER_SIZE];
+    let mut returned = 0;
+    let ok = unsafe {
+        DeviceIoControl(
+            file.as_raw_handle() as _,
+            FSCTL_GET_REPARSE_POINT,
+            ptr::null_mut(),
+            0,
+            buf.as_mut_ptr() as *mut _,
+            buf.len() as DWORD,
+            &mut returned,
+            ptr::null_mut(),
+        )
+    };
+    if ok == 0 {
+        return Err(io::Error::last_os_error());
+    }
+    buf.truncate(returned as usize);
+    Ok(buf)
+}
+
+/// Builds the substitute/print name path buffer shared by mount points and
+/// symlinks, returning `(bytes, substitute_offset, substitute_len, print_offset, print_len)`.
+///
+/// Operates on already-wide (UTF-16) code units, rather than `OsStr`/`str`,
+/// so callers never need a lossy `to_string_lossy()` round-trip to build or
+/// manipulate the names.
+fn build_path_buffer(substitute: &[u16], print: &[u16]) -> (Vec<u8>, WORD, WORD, WORD, WORD) {
+    let substitute_len = (substitute.len() * 2) as WORD;
+    let print_len = (print.len() * 2) as WORD;
+    let substitute_offset: WORD = 0;
+    // +2 for the substitute name's own NUL terminator.
+    let print_offset = substitute_len + 2;
+
+    let mut path_buffer = Vec::new();
+    push_u16s(&mut path_buffer, substitute);
+    push_u16s(&mut path_buffer, &[0]);
+    push_u16s(&mut path_buffer, print);
+    push_u16s(&mut path_buffer, &[0]);
+
+    (path_buffer, substitute_offset, substitute_len, print_offset, print_len)
+}
+
+/// The `\\?\` Win32 extended-length prefix, as UTF-16 code units.
+const EXTENDED_PATH_PREFIX: [u16; 4] = [b'\\' as u16, b'\\' as u16, b'?' as u16, b'\\' as u16];
+
+/// Strips a leading `\\?\` from `path`, if present, without a lossy
+/// UTF-8 round-trip.
+fn strip_extended_prefix(path: &[u16]) -> &[u16] {
+    if path.starts_with(&EXTENDED_PATH_PREFIX) {
+        &path[EXTENDED_PATH_PREFIX.len()..]
+    } else {
+        path
+    }
+}
+
+fn build_mount_point_buffer(target: &Path) -> Vec<u8> {
+    // `target` may already be in `\\?\`-prefixed extended-length form (see
+    // `Options::create`); the substitute name always uses the `\??\` NT
+    // namespace prefix instead, so strip one before applying the other.
+    let target_wide = to_u16s(target.as_os_str());
+    let print = strip_extended_prefix(&target_wide);
+
+    let mut substitute = to_u16s(r"\??\");
+    substitute.extend_from_slice(print);
+
+    let (path_buffer, substitute_offset, substitute_len, print_offset, print_len) =
+        build_path_buffer(&substitute, print);
+
+    let header = MountPointHeader {
+        ReparseTag: IO_REPARSE_TAG_MOUNT_POINT,
+        ReparseDataLength: (8 + path_buffer.len()) as WORD,
+        Reserved: 0,
+        SubstituteNameOffset: substitute_offset,
+        SubstituteNameLength: substitute_len,
+        PrintNameOffset: print_offset,
+        PrintNameLength: print_len,
+    };
+
+    let mut buf = header_bytes(&header).to_vec();
+    buf.extend_from_slice(&path_buffer);
+    buf
+}
+
+/// Builds a `REPARSE_DATA_BUFFER` for `IO_REPARSE_TAG_SYMLINK`. Relative
+/// targets are stored verbatim with `SYMLINK_FLAG_RELATIVE` set; absolute
+/// targets get the usual `\??\` substitute-name prefix, stripping a
+/// pre-existing `\\?\` prefix first just like the mount-point buffer does.
+fn build_symlink_buffer(target: &Path) -> Vec<u8> {
+    let relative = target.is_relative();
+    let target_wide = to_u16s(target.as_os_str());
+    let print: &[u16] = if relative {
+        &target_wide
+    } else {
+        strip_extended_prefix(&target_wide)
+    };
+
+    let substitute: Vec<u16> = if relative {
+        target_wide.clone()
+    } else {
+        let mut s = to_u16s(r"\??\");
+        s.extend_from_slice(print);
+        s
+    };
+
+    let (path_buffer, substitute_offset, substitute_len, print_offset, print_len) =
+        build_path_buffer(&substitute, print);
+
+    let header = SymlinkHeader {
+        ReparseTag: IO_REPARSE_TAG_SYMLINK,
+        ReparseDataLength: (12 + path_buffer.len()) as WORD,
+        Reserved: 0,
+        SubstituteNameOffset: substitute_offset,
+        SubstituteNameLength: substitute_len,
+        PrintNameOffset: print_offset,
+        PrintNameLength: print_len,
+        Flags: if relative { SYMLINK_FLAG_RELATIVE } else { 0 },
+    };
+
+    let mut buf = header_bytes(&header).to_vec();
+    buf.extend_from_slice(&path_buffer);
+    buf
+}
+
+/// Reads a name (substitute or print) out of a raw reparse buffer, given
+/// the header size that precedes the path buffer.
+///
+/// `offset`/`len` are only meaningful for name-surrogate tags; for other
+/// tags (e.g. dedup/WIM) they are an arbitrary `DataBuffer`'s bytes
+/// reinterpreted as offsets, so a range that falls outside `raw` is
+/// expected rather than a bug — return an empty name instead of panicking.
+fn read_name(raw: &[u8], header_len: usize, offset: WORD, len: WORD) -> OsString {
+    let start = header_len + offset as usize;
+    let end = start + len as usize;
+    let slice = match raw.get(start..end) {
+        Some(slice) => slice,
+        None => return OsString::new(),
+    };
+    let wide: Vec<u16> = slice
+        .chunks_exact(2)
+        .map(|b| u16::from_le_bytes([b[0], b[1]]))
+        .collect();
+    OsString::from_wide(&wide)
+}
+
+pub fn create(target: &Path, junction: &Path) -> io::Result<()> {
+    fs::create_dir(junction)?;
+    let file = open_reparse_point(junction, true)?;
+    let mut buf = build_mount_point_buffer(target);
+    set_reparse_point(&file, &mut buf)
+}
+
+fn to_absolute(path: &Path) -> io::Result<PathBuf> {
+    if path.is_absolute() {
+        Ok(path.to_path_buf())
+    } else {
+        Ok(std::env::current_dir()?.join(path))
+    }
+}
+
+/// Normalizes `path` to the `\\?\`-prefixed Win32 extended-length form, so
+/// it can round-trip through `CreateFileW`/`CreateDirectoryW` regardless of
+/// its length.
+fn extended_length_path(path: &Path) -> io::Result<PathBuf> {
+    let path = to_absolute(path)?;
+    // The verbatim form only accepts `\` as a separator.
+    let s = path.as_os_str().to_string_lossy().replace('/', r"\");
+    if s.starts_with(r"\\?\") {
+        return Ok(PathBuf::from(s));
+    }
+    if let Some(rest) = s.strip_prefix(r"\\") {
+        return Ok(PathBuf::from(format!(r"\\?\UNC\{}", rest)));
+    }
+    Ok(PathBuf::from(format!(r"\\?\{}", s)))
+}
+
+/// Removes `junction` if it is an empty directory or an existing junction.
+/// If `junction` doesn't exist, this is a no-op; otherwise whatever error
+/// `RemoveDirectoryW` reports is propagated as-is — e.g. `ERROR_DIR_NOT_EMPTY`
+/// for a non-empty directory, or an access/type error for a plain file —
+/// rather than being papered over as `ERROR_ALREADY_EXISTS`.
+fn remove_existing_link(junction: &Path) -> io::Result<()> {
+    match fs::remove_dir(junction) {
+        Ok(()) => Ok(()),
+        Err(ref e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(e),
+    }
+}
+
+/// Implementation behind [`crate::Options::create`].
+pub fn create_with_options(target: &Path, junction: &Path, overwrite: bool) -> io::Result<()> {
+    let target = extended_length_path(target)?;
+    let junction = extended_length_path(junction)?;
+
+    if overwrite {
+        remove_existing_link(&junction)?;
+    }
+
+    create(&target, &junction)
+}
+
+/// Creates an `IO_REPARSE_TAG_SYMLINK` reparse point at `link` pointing at
+/// `target`. Unlike [`create`], `target` is not required to exist and may
+/// be relative or point at a file.
+fn create_symlink(target: &Path, link: &Path, dir: bool) -> io::Result<()> {
+    if dir {
+        fs::create_dir(link)?;
+    } else {
+        File::create(link)?;
+    }
+    let file = open_reparse_point(link, true)?;
+    let mut buf = build_symlink_buffer(target);
+    set_reparse_point(&file, &mut buf)
+}
+
+pub fn symlink_dir(target: &Path, link: &Path) -> io::Result<()> {
+    create_symlink(target, link, true)
+}
+
+pub fn symlink_file(target: &Path, link: &Path) -> io::Result<()> {
+    create_symlink(target, link, false)
+}
+
+pub fn delete(junction: &Path) -> io::Result<()> {
+    // Opening fails with `NotFound` if `junction` doesn't exist; a path that
+    // isn't a reparse point is rejected by `FSCTL_DELETE_REPARSE_POINT`
+    // itself with `ERROR_NOT_A_REPARSE_POINT`.
+    let file = open_reparse_point(junction, true)?;
+    let header = MountPointHeader {
+        ReparseTag: IO_REPARSE_TAG_MOUNT_POINT,
+        ReparseDataLength: 0,
+        Reserved: 0,
+        SubstituteNameOffset: 0,
+        SubstituteNameLength: 0,
+        PrintNameOffset: 0,
+        PrintNameLength: 0,
+    };
+    let mut buf = header_bytes(&header).to_vec();
+    let mut returned = 0;
+    let ok = unsafe {
+        DeviceIoControl(
+            file.as_raw_handle() as _,
+            FSCTL_DELETE_REPARSE_POINT,
+            buf.as_mut_ptr() as *mut _,
+            8,
+            ptr::null_mut(),
+            0,
+            &mut returned,
+            ptr::null_mut(),
+        )
+    };
+    if ok == 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+/// Recursively removes `path`, treating reparse points (junctions and
+/// symlinks) as opaque leaves rather than descending into whatever they
+/// point at.
+///
+/// If `path` itself is a reparse point, only the link is removed, just
+/// like a nested one encountered while recursing.
+pub fn remove_dir_all(path: &Path) -> io::Result<()> {
+    let meta = fs::symlink_metadata(path)?;
+    if meta.file_attributes() & FILE_ATTRIBUTE_REPARSE_POINT != 0 {
+        return remove_reparse_point(path, &meta);
+    }
+    remove_dir_all_contents(path)
+}
+
+/// Removes a single reparse point entry (`path`) without following it,
+/// picking `RemoveDirectoryW` or `DeleteFileW` from the raw
+/// `FILE_ATTRIBUTE_DIRECTORY` bit since `FileType::is_dir()` never follows
+/// reparse points (so it is `false` for *every* name-surrogate reparse
+/// point, junctions included, not just file symlinks).
+fn remove_reparse_point(path: &Path, meta: &fs::Metadata) -> io::Result<()> {
+    if meta.file_attributes() & FILE_ATTRIBUTE_DIRECTORY != 0 {
+        fs::remove_dir(path)
+    } else {
+        fs::remove_file(path)
+    }
+}
+
+fn remove_dir_all_contents(path: &Path) -> io::Result<()> {
+    for entry in fs::read_dir(path)? {
+        let entry = entry?;
+        let meta = entry.metadata()?;
+        let entry_path = entry.path();
+        if meta.file_attributes() & FILE_ATTRIBUTE_REPARSE_POINT != 0 {
+            remove_reparse_point(&entry_path, &meta)?;
+        } else if meta.is_dir() {
+            remove_dir_all_contents(&entry_path)?;
+        } else {
+            fs::remove_file(&entry_path)?;
+        }
+    }
+    fs::remove_dir(path)
+}
+
+pub fn exists(junction: &Path) -> io::Result<bool> {
+    // Route through the same reparse-point read `get_target` uses instead
+    // of short-circuiting on the `FILE_ATTRIBUTE_REPARSE_POINT` bit: a path
+    // that exists but isn't a reparse point at all must still surface
+    // `ERROR_NOT_A_REPARSE_POINT`, matching this function's established
+    // contract.
+    match read_reparse_point(junction) {
+        Ok(reparse) => Ok(reparse.kind == crate::ReparseTag::MountPoint),
+        Err(ref e) if e.kind() == io::ErrorKind::NotFound => Ok(false),
+        Err(e) => Err(e),
+    }
+}
+
+fn read_u16_at(raw: &[u8], at: usize) -> WORD {
+    raw.get(at..at + 2)
+        .map(|b| u16::from_le_bytes([b[0], b[1]]))
+        .unwrap_or(0)
+}
+
+fn read_u32_at(raw: &[u8], at: usize) -> u32 {
+    raw.get(at..at + 4)
+        .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+        .unwrap_or(0)
+}
+
+/// Reads and parses an arbitrary reparse point, regardless of its tag.
+///
+/// Only `IO_REPARSE_TAG_MOUNT_POINT` and `IO_REPARSE_TAG_SYMLINK` are
+/// known to use the substitute/print-name layout read here; for any other
+/// tag these fields are read best-effort (empty if the buffer is too
+/// short to plausibly hold them) rather than assumed correct.
+pub fn read_reparse_point(path: &Path) -> io::Result<crate::ReparsePoint> {
+    let file = open_reparse_point(path, false)?;
+    let raw = get_reparse_data(&file)?;
+
+    let tag = read_u32_at(&raw, 0);
+    let substitute_offset = read_u16_at(&raw, 8);
+    let substitute_len = read_u16_at(&raw, 10);
+    let print_offset = read_u16_at(&raw, 12);
+    let print_len = read_u16_at(&raw, 14);
+
+    let (kind, header_len, relative) = match tag {
+        IO_REPARSE_TAG_MOUNT_POINT => (
+            crate::ReparseTag::MountPoint,
+            mem::size_of::<MountPointHeader>(),
+            false,
+        ),
+        IO_REPARSE_TAG_SYMLINK => {
+            let flags = read_u32_at(&raw, 16);
+            (
+                crate::ReparseTag::Symlink,
+                mem::size_of::<SymlinkHeader>(),
+                flags & SYMLINK_FLAG_RELATIVE != 0,
+            )
+        }
+        other => (
+            // Third-party tags (dedup, WIM, ...) don't necessarily use the
+            // mount-point layout at all; `read_name` returns an empty name
+            // rather than panicking when the offsets don't fit `raw`.
+            crate::ReparseTag::Other(other),
+            mem::size_of::<MountPointHeader>(),
+            false,
+        ),
+    };
+
+    let substitute_name = PathBuf::from(read_name(&raw, header_len, substitute_offset, substitute_len));
+    let print_name = PathBuf::from(read_name(&raw, header_len, print_offset, print_len));
+
+    Ok(crate::ReparsePoint {
+        tag,
+        kind,
+        substitute_name,
+        print_name,
+        relative,
+    })
+}
+
+/// Gets the substitute name of a mount point, stripped of its `\??\` prefix.
+pub fn get_target(junction: &Path) -> io::Result<PathBuf> {
+    let reparse = read_reparse_point(junction)?;
+    if reparse.kind != crate::ReparseTag::MountPoint {
+        return Err(io::Error::from_raw_os_error(
+            winapi::shared::winerror::ERROR_NOT_A_REPARSE_POINT as i32,
+        ));
+    }
+
+    let name = reparse.substitute_name.to_string_lossy().into_owned();
+    let name = name.strip_prefix(r"\??\").unwrap_or(&name);
+    Ok(PathBuf::from(name))
+}
@@ -40,6 +40,68 @@ where
     crate::internals::create(target.as_ref(), junction.as_ref())
 }
 
+/// Creates an NTFS symbolic link to a directory.
+///
+/// Unlike [`create`], `target` does not need to exist and may be a relative
+/// path, in which case the link is resolved relative to the directory that
+/// contains `link`.
+///
+/// N.B. Only works on NTFS.
+///
+/// # Example
+///
+/// ```rust
+/// use std::io;
+/// use std::path::Path;
+/// # use std::fs;
+/// # use junction::symlink_dir;
+/// fn main() -> io::Result<()> {
+///     let tmpdir = tempfile::tempdir()?;
+///     let target = tmpdir.path().join("target");
+///     let link = tmpdir.path().join("link");
+///     fs::create_dir_all(&target)?;
+///     symlink_dir(&target, &link)
+/// }
+/// ```
+pub fn symlink_dir<P, Q>(target: P, link: Q) -> io::Result<()>
+where
+    P: AsRef<Path>,
+    Q: AsRef<Path>,
+{
+    crate::internals::symlink_dir(target.as_ref(), link.as_ref())
+}
+
+/// Creates an NTFS symbolic link to a file.
+///
+/// Unlike [`create`], `target` does not need to exist and may be a relative
+/// path, in which case the link is resolved relative to the directory that
+/// contains `link`.
+///
+/// N.B. Only works on NTFS.
+///
+/// # Example
+///
+/// ```rust
+/// use std::io;
+/// use std::path::Path;
+/// # use std::fs::{self, File};
+/// # use junction::symlink_file;
+/// fn main() -> io::Result<()> {
+///     let tmpdir = tempfile::tempdir()?;
+///     let target = tmpdir.path().join("target");
+///     let link = tmpdir.path().join("link");
+///     File::create(&target)?;
+///     symlink_file(&target, &link)
+/// }
+/// ```
+pub fn symlink_file<P, Q>(target: P, link: Q) -> io::Result<()>
+where
+    P: AsRef<Path>,
+    Q: AsRef<Path>,
+{
+    crate::internals::symlink_file(target.as_ref(), link.as_ref())
+}
+
 /// Deletes a `junction` reparse point from the specified file or directory.
 ///
 /// N.B. Only works on NTFS.
@@ -101,17 +163,160 @@ pub fn get_target<P: AsRef<Path>>(junction: P) -> io::Result<PathBuf> {
     crate::internals::get_target(junction.as_ref())
 }
 
+/// The kind of reparse point a [`ReparsePoint`] was read from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReparseTag {
+    /// `IO_REPARSE_TAG_MOUNT_POINT`, i.e. a junction.
+    MountPoint,
+    /// `IO_REPARSE_TAG_SYMLINK`.
+    Symlink,
+    /// Any other reparse tag, such as those used by deduplication or WIM,
+    /// carrying the raw tag value.
+    Other(u32),
+}
+
+/// The parsed contents of an arbitrary NTFS reparse point, as returned by
+/// [`read_reparse_point`].
+#[derive(Debug, Clone)]
+pub struct ReparsePoint {
+    /// The raw `ReparseTag` field, e.g. `IO_REPARSE_TAG_MOUNT_POINT`.
+    pub tag: u32,
+    /// `tag`, discriminated into the cases this crate knows how to handle.
+    pub kind: ReparseTag,
+    /// The substitute name, e.g. `\??\C:\Users\Default`.
+    pub substitute_name: PathBuf,
+    /// The user-facing print name, e.g. `C:\Users\Default`.
+    pub print_name: PathBuf,
+    /// Whether `SYMLINK_FLAG_RELATIVE` is set. Always `false` for reparse
+    /// points other than symlinks.
+    pub relative: bool,
+}
+
+/// Reads and parses an arbitrary reparse point, regardless of its tag.
+///
+/// Where [`get_target`] only understands junctions, this inspects any
+/// reparse point — including symlinks and third-party tags — and lets the
+/// caller decide how to handle it instead of failing outright.
+///
+/// N.B. Only works on NTFS.
+///
+/// # Example
+///
+/// ```rust
+/// use std::io;
+/// # use junction::{create, read_reparse_point, ReparseTag};
+/// fn main() -> io::Result<()> {
+///     let tmpdir = tempfile::tempdir()?;
+///     let target = tmpdir.path().join("target");
+///     let junction = tmpdir.path().join("junction");
+///     # std::fs::create_dir_all(&target)?;
+///     create(&target, &junction)?;
+///     let reparse = read_reparse_point(&junction)?;
+///     assert_eq!(reparse.kind, ReparseTag::MountPoint);
+///     Ok(())
+/// }
+/// ```
+pub fn read_reparse_point<P: AsRef<Path>>(path: P) -> io::Result<ReparsePoint> {
+    crate::internals::read_reparse_point(path.as_ref())
+}
+
+/// A builder for creating a junction point with extra options, analogous
+/// to [`std::fs::OpenOptions`].
+///
+/// # Example
+///
+/// ```rust
+/// use std::io;
+/// # use std::fs;
+/// # use junction::Options;
+/// fn main() -> io::Result<()> {
+///     let tmpdir = tempfile::tempdir()?;
+///     let target = tmpdir.path().join("target");
+///     let junction = tmpdir.path().join("junction");
+///     fs::create_dir_all(&target)?;
+///     fs::create_dir_all(&junction)?;
+///     Options::new().overwrite(true).create(&target, &junction)
+/// }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Options {
+    overwrite: bool,
+}
+
+impl Options {
+    /// Creates a blank set of options, matching [`create`]'s behavior:
+    /// the junction path must not already exist.
+    pub fn new() -> Self {
+        Options::default()
+    }
+
+    /// If set, an existing empty directory or junction at the destination
+    /// is removed before creating the new junction, instead of failing
+    /// with `ERROR_ALREADY_EXISTS`.
+    pub fn overwrite(mut self, overwrite: bool) -> Self {
+        self.overwrite = overwrite;
+        self
+    }
+
+    /// Creates a junction point from `target` to `junction` per these
+    /// options, normalizing both paths to the `\\?\` extended-length form
+    /// so targets longer than `MAX_PATH` work.
+    ///
+    /// N.B. Only works on NTFS.
+    pub fn create<P, Q>(&self, target: P, junction: Q) -> io::Result<()>
+    where
+        P: AsRef<Path>,
+        Q: AsRef<Path>,
+    {
+        crate::internals::create_with_options(target.as_ref(), junction.as_ref(), self.overwrite)
+    }
+}
+
+/// Recursively removes a directory and all of its contents, without
+/// following junctions or symlinks found along the way.
+///
+/// N.B. Only works on NTFS.
+///
+/// Unlike [`std::fs::remove_dir_all`], this will never delete the target a
+/// junction or symlink points at — the link itself is removed and the
+/// subtree underneath it is left untouched.
+///
+/// # Example
+///
+/// ```rust
+/// use std::io;
+/// # use std::fs;
+/// # use junction::{create, remove_dir_all};
+/// fn main() -> io::Result<()> {
+///     let tmpdir = tempfile::tempdir()?;
+///     let target = tmpdir.path().join("target");
+///     let dir = tmpdir.path().join("dir");
+///     let junction = dir.join("junction");
+///     fs::create_dir_all(&target)?;
+///     fs::create_dir_all(&dir)?;
+///     create(&target, &junction)?;
+///     remove_dir_all(&dir)?;
+///     assert!(target.exists());
+///     Ok(())
+/// }
+/// ```
+pub fn remove_dir_all<P: AsRef<Path>>(path: P) -> io::Result<()> {
+    crate::internals::remove_dir_all(path.as_ref())
+}
+
 #[cfg(test)]
 mod tests {
     use std::{
         fs::{self, File},
         io::{self, Write},
         os::windows::fs::symlink_file,
+        path::Path,
     };
 
     // https://docs.microsoft.com/en-us/windows/desktop/debug/system-error-codes
     const ERROR_NOT_A_REPARSE_POINT: i32 = 0x1126;
     const ERROR_ALREADY_EXISTS: i32 = 0xb7;
+    const ERROR_DIR_NOT_EMPTY: i32 = 0x91;
 
     macro_rules! check {
         ($e:expr) => {
@@ -185,6 +390,114 @@ mod tests {
         assert!(canary.exists());
     }
 
+    #[test]
+    fn create_symlink_dir_absolute() {
+        let tmpdir = create_tempdir();
+        let target = tmpdir.path().join("target");
+        let link = tmpdir.path().join("link");
+
+        check!(fs::create_dir_all(&target));
+        check!(check!(File::create(target.join("file"))).write_all(b"foo"));
+
+        check!(super::symlink_dir(&target, &link));
+        assert!(link.is_dir());
+        assert!(link.join("file").exists());
+    }
+
+    #[test]
+    fn create_symlink_dir_relative() {
+        let tmpdir = create_tempdir();
+        let target = tmpdir.path().join("target");
+        let link = tmpdir.path().join("link");
+
+        check!(fs::create_dir_all(&target));
+
+        check!(super::symlink_dir(Path::new("target"), &link));
+        assert!(link.is_dir());
+    }
+
+    #[test]
+    fn create_symlink_file() {
+        let tmpdir = create_tempdir();
+        let target = tmpdir.path().join("target");
+        let link = tmpdir.path().join("link");
+
+        check!(check!(File::create(&target)).write_all(b"foo"));
+
+        check!(super::symlink_file(&target, &link));
+        assert!(link.is_file());
+        assert_eq!(check!(fs::read(&link)), b"foo");
+    }
+
+    #[test]
+    fn create_symlink_target_no_exist() {
+        let tmpdir = create_tempdir();
+        let target = tmpdir.path().join("target");
+        let link = tmpdir.path().join("link");
+
+        check!(super::symlink_dir(&target, &link));
+    }
+
+    #[test]
+    fn remove_dir_all_does_not_follow_junction() {
+        let tmpdir = create_tempdir();
+        let d1 = tmpdir.path().join("d1"); // "d1"
+        let dt = d1.join("t"); // "d1/t"
+        let dtt = dt.join("t"); // "d1/t/t"
+        let d2 = tmpdir.path().join("d2"); // "d2"
+        let canary = d2.join("do_not_delete"); // "d2/do_not_delete"
+
+        check!(fs::create_dir_all(&dtt));
+        check!(fs::create_dir_all(&d2));
+        check!(check!(File::create(&canary)).write_all(b"foo"));
+
+        check!(super::create(&d2, &dt.join("d2"))); // "d1/t/d2" -> "d2"
+
+        check!(super::remove_dir_all(&d1));
+
+        assert!(!d1.is_dir());
+        assert!(canary.exists());
+    }
+
+    #[test]
+    fn remove_dir_all_does_not_follow_symlink() {
+        let tmpdir = create_tempdir();
+        let link = tmpdir.path().join("link");
+        let dir = tmpdir.path().join("dir");
+        let canary = dir.join("do_not_delete");
+        check!(fs::create_dir_all(&dir));
+        check!(check!(File::create(&canary)).write_all(b"foo"));
+        check!(super::create(&dir, &link));
+
+        let d1 = tmpdir.path().join("d1");
+        check!(fs::create_dir_all(&d1));
+        let _ = symlink_file(&canary, &d1.join("canary"));
+
+        check!(super::remove_dir_all(&d1));
+
+        assert!(!d1.is_dir());
+        assert!(canary.exists());
+    }
+
+    #[test]
+    fn remove_dir_all_of_junction_root() {
+        // passing a junction as the root itself must not recurse into
+        // its target.
+        let tmpdir = create_tempdir();
+        let target = tmpdir.path().join("target");
+        let canary = target.join("do_not_delete");
+        let junction = tmpdir.path().join("junction");
+
+        check!(fs::create_dir_all(&target));
+        check!(check!(File::create(&canary)).write_all(b"foo"));
+        check!(super::create(&target, &junction));
+
+        check!(super::remove_dir_all(&junction));
+
+        assert!(!junction.exists());
+        assert!(canary.exists());
+    }
+
     #[test]
     fn create_directory_exist_before() {
         let tmpdir = create_tempdir();
@@ -200,6 +513,67 @@ mod tests {
         }
     }
 
+    #[test]
+    fn options_overwrite_existing_empty_dir() {
+        let tmpdir = create_tempdir();
+
+        let target = tmpdir.path().join("target");
+        let junction = tmpdir.path().join("junction");
+
+        check!(fs::create_dir_all(&target));
+        check!(fs::create_dir_all(&junction));
+
+        check!(super::Options::new().overwrite(true).create(&target, &junction));
+        assert_eq!(&check!(super::get_target(&junction)), &target);
+    }
+
+    #[test]
+    fn options_overwrite_existing_junction() {
+        let tmpdir = create_tempdir();
+
+        let target = tmpdir.path().join("target");
+        let other_target = tmpdir.path().join("other_target");
+        let junction = tmpdir.path().join("junction");
+
+        check!(fs::create_dir_all(&target));
+        check!(fs::create_dir_all(&other_target));
+        check!(super::create(&other_target, &junction));
+
+        check!(super::Options::new().overwrite(true).create(&target, &junction));
+        assert_eq!(&check!(super::get_target(&junction)), &target);
+    }
+
+    #[test]
+    fn options_without_overwrite_fails_if_exists() {
+        let tmpdir = create_tempdir();
+
+        let target = tmpdir.path().join("target");
+        let junction = tmpdir.path().join("junction");
+
+        check!(fs::create_dir_all(&junction));
+
+        match super::Options::new().create(&target, &junction) {
+            Err(ref e) if e.raw_os_error() == Some(ERROR_ALREADY_EXISTS) => (),
+            _ => panic!("directory exists before creating"),
+        }
+    }
+
+    #[test]
+    fn options_overwrite_of_non_empty_dir_fails() {
+        let tmpdir = create_tempdir();
+
+        let target = tmpdir.path().join("target");
+        let junction = tmpdir.path().join("junction");
+
+        check!(fs::create_dir_all(&junction));
+        check!(check!(File::create(junction.join("file"))).write_all(b"foo"));
+
+        match super::Options::new().overwrite(true).create(&target, &junction) {
+            Err(ref e) if e.raw_os_error() == Some(ERROR_DIR_NOT_EMPTY) => (),
+            _ => panic!("non-empty directory should not be removed"),
+        }
+    }
+
     #[test]
     fn create_target_no_exist() {
         let tmpdir = create_tempdir();
@@ -296,6 +670,37 @@ mod tests {
         assert!(junction.exists(), "directory should not be deleted");
     }
 
+    #[test]
+    fn read_reparse_point_of_junction() {
+        let tmpdir = create_tempdir();
+        let target = tmpdir.path().join("target");
+        let junction = tmpdir.path().join("junction");
+
+        check!(fs::create_dir_all(&target));
+        check!(super::create(&target, &junction));
+
+        let reparse = check!(super::read_reparse_point(&junction));
+        assert_eq!(reparse.kind, super::ReparseTag::MountPoint);
+        assert_eq!(reparse.tag, 0xA000_0003);
+        assert_eq!(reparse.print_name, target);
+        assert!(!reparse.relative);
+    }
+
+    #[test]
+    fn read_reparse_point_of_relative_symlink() {
+        let tmpdir = create_tempdir();
+        let target = tmpdir.path().join("target");
+        let link = tmpdir.path().join("link");
+
+        check!(fs::create_dir_all(&target));
+        check!(super::symlink_dir(Path::new("target"), &link));
+
+        let reparse = check!(super::read_reparse_point(&link));
+        assert_eq!(reparse.kind, super::ReparseTag::Symlink);
+        assert!(reparse.relative);
+        assert_eq!(reparse.print_name, Path::new("target"));
+    }
+
     #[test]
     fn get_target_user_dirs() {
         // junction